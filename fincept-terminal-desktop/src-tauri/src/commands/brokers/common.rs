@@ -3,6 +3,7 @@
 
 use reqwest::header::{HeaderMap, HeaderValue};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 // ============================================================================
 // Common Response Types
@@ -44,3 +45,139 @@ pub fn create_common_headers() -> HeaderMap {
     );
     headers
 }
+
+/// Format epoch milliseconds as a UTC ISO 8601 string, e.g. "2024-01-01T00:00:00.000Z"
+///
+/// Returns an empty string for values outside the representable date range.
+pub fn to_iso8601(millis: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(millis)
+        .map(|dt| dt.to_rfc3339_opts(chrono::SecondsFormat::Millis, true))
+        .unwrap_or_default()
+}
+
+/// Normalise an epoch timestamp to milliseconds
+///
+/// Values below 1e11 (before ~1973 in milliseconds) are taken to be seconds.
+pub fn epoch_to_millis(ts: i64) -> i64 {
+    if ts.abs() < 100_000_000_000 { ts * 1000 } else { ts }
+}
+
+/// Whether a JSON key names an epoch timestamp field
+fn is_timestamp_key(key: &str) -> bool {
+    key == "timestamp" || key.ends_with("_at") || key.ends_with("_time")
+}
+
+/// Add a `<key>_iso` string next to every integer timestamp field
+///
+/// Walks objects and arrays recursively. Fields named `timestamp`, `*_at` or
+/// `*_time` holding an integer are epoch seconds or milliseconds (see
+/// `epoch_to_millis`); the raw value is left in place for code that needs it.
+pub fn add_iso_timestamps(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            let formatted: Vec<(String, String)> = map.iter()
+                .filter(|(key, _)| is_timestamp_key(key))
+                .filter_map(|(key, raw)| {
+                    raw.as_i64().map(|ts| (format!("{}_iso", key), to_iso8601(epoch_to_millis(ts))))
+                })
+                .collect();
+            for child in map.values_mut() {
+                add_iso_timestamps(child);
+            }
+            for (key, iso) in formatted {
+                map.insert(key, Value::String(iso));
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(add_iso_timestamps),
+        _ => {}
+    }
+}
+
+/// Round to `places` decimal places, resolving ties to the even digit
+///
+/// Works on the shortest decimal representation of the float, so `2.675`
+/// rounds to `2.68` even though its binary value is slightly below it.
+pub fn round_half_even(value: f64, places: u32) -> f64 {
+    if !value.is_finite() {
+        return value;
+    }
+
+    // `{:e}` yields the shortest round-trip digits, e.g. "-2.675e0"
+    let formatted = format!("{:e}", value.abs());
+    let (mantissa, exponent) = formatted.split_once('e').unwrap_or((&formatted, "0"));
+    let exponent: i64 = exponent.parse().unwrap_or(0);
+    let digits: Vec<u8> = mantissa.bytes().filter(|b| b.is_ascii_digit()).map(|b| b - b'0').collect();
+
+    // Number of significant digits that survive rounding
+    let keep = exponent + 1 + i64::from(places);
+    if keep >= digits.len() as i64 {
+        return value;
+    }
+    if keep < 0 {
+        return 0.0_f64.copysign(value);
+    }
+
+    let keep = keep as usize;
+    let mut kept: Vec<u8> = digits[..keep].to_vec();
+    let rest = &digits[keep..];
+    let round_up = match rest[0].cmp(&5) {
+        std::cmp::Ordering::Greater => true,
+        std::cmp::Ordering::Less => false,
+        std::cmp::Ordering::Equal => {
+            rest[1..].iter().any(|d| *d != 0) || kept.last().is_some_and(|d| d % 2 == 1)
+        }
+    };
+
+    if round_up {
+        let mut carry = true;
+        for digit in kept.iter_mut().rev() {
+            if *digit == 9 {
+                *digit = 0;
+            } else {
+                *digit += 1;
+                carry = false;
+                break;
+            }
+        }
+        if carry {
+            kept.insert(0, 1);
+        }
+    }
+
+    let integer: String = if kept.is_empty() {
+        "0".to_string()
+    } else {
+        kept.iter().map(|d| char::from(b'0' + d)).collect()
+    };
+    let rounded: f64 = format!("{}e-{}", integer, places).parse().unwrap_or(value.abs());
+    rounded.copysign(value)
+}
+
+/// Whether a JSON key names a monetary or percentage field
+fn is_roundable_key(key: &str) -> bool {
+    const SUFFIXES: [&str; 8] = ["_percent", "_pnl", "_value", "_price", "_basis", "_balance", "_power", "_change"];
+    key == "pnl" || SUFFIXES.iter().any(|suffix| key.ends_with(suffix))
+}
+
+/// Round every monetary and percentage field to `places` decimal places
+///
+/// Walks objects and arrays recursively. Fields named `pnl` or ending in
+/// `_percent`, `_pnl`, `_value`, `_price`, `_basis`, `_balance`, `_power` or
+/// `_change` are rounded with `round_half_even`. Quantities, weights and
+/// timestamps are left untouched.
+pub fn round_monetary_fields(value: &mut Value, places: u32) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                match child.as_f64() {
+                    Some(number) if child.is_f64() && is_roundable_key(key) => {
+                        *child = serde_json::json!(round_half_even(number, places));
+                    }
+                    _ => round_monetary_fields(child, places),
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| round_monetary_fields(item, places)),
+        _ => {}
+    }
+}
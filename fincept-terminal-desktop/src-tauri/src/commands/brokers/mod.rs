@@ -44,7 +44,7 @@
 //! ├── alpaca.rs      - Alpaca US broker (43 commands)
 //! ├── ibkr.rs        - Interactive Brokers US broker (35+ commands)
 //! ├── tradier.rs     - Tradier US broker (20+ commands)
-//! ├── autotrade.rs   - Autotrade broker (4 commands)
+//! ├── autotrade.rs   - Autotrade broker (105 commands)
 //! └── mod.rs         - This file (re-exports)
 //! ```
 
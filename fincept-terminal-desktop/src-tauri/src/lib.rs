@@ -2469,6 +2469,107 @@ pub fn run() {
             commands::brokers::autotrade_get_account_summary,
             commands::brokers::autotrade_get_performance,
             commands::brokers::autotrade_get_orders,
+            commands::brokers::autotrade_get_fills,
+            commands::brokers::autotrade_get_position_pnl_detail,
+            commands::brokers::autotrade_compute_daily_changes,
+            commands::brokers::autotrade_get_symbol_capabilities,
+            commands::brokers::autotrade_set_default_period,
+            commands::brokers::autotrade_subscribe_quotes,
+            commands::brokers::autotrade_unsubscribe_quotes,
+            commands::brokers::autotrade_get_options_greeks,
+            commands::brokers::autotrade_benchmark_placement,
+            commands::brokers::autotrade_set_auth_tokens,
+            commands::brokers::autotrade_refresh_token,
+            commands::brokers::autotrade_get_turnover,
+            commands::brokers::autotrade_get_account_status,
+            commands::brokers::autotrade_place_order,
+            commands::brokers::autotrade_set_timeouts,
+            commands::brokers::autotrade_simulate_add,
+            commands::brokers::autotrade_set_strict_validation,
+            commands::brokers::autotrade_get_orders_grouped,
+            commands::brokers::autotrade_get_return_attribution,
+            commands::brokers::autotrade_get_fee_schedule,
+            commands::brokers::autotrade_cancel_orders_where,
+            commands::brokers::autotrade_get_order_history,
+            commands::brokers::autotrade_get_all_order_history,
+            commands::brokers::autotrade_compute_realized,
+            commands::brokers::autotrade_get_consolidated_positions,
+            commands::brokers::autotrade_set_api_prefix,
+            commands::brokers::autotrade_export_orders_csv,
+            commands::brokers::autotrade_set_rate_limit,
+            commands::brokers::autotrade_get_volatility,
+            commands::brokers::autotrade_ensure_position,
+            commands::brokers::autotrade_find_duplicate_orders,
+            commands::brokers::autotrade_set_iso_timestamps,
+            commands::brokers::autotrade_preview_buying_power,
+            commands::brokers::autotrade_get_movers,
+            commands::brokers::autotrade_compute_rebalance,
+            commands::brokers::autotrade_longpoll_pnl,
+            commands::brokers::autotrade_get_holding_periods,
+            commands::brokers::autotrade_save_baseline,
+            commands::brokers::autotrade_compare_to_baseline,
+            commands::brokers::autotrade_set_etag_caching,
+            commands::brokers::autotrade_get_portfolio_yield,
+            commands::brokers::autotrade_raw_get,
+            commands::brokers::autotrade_place_orders,
+            commands::brokers::autotrade_get_exposure,
+            commands::brokers::autotrade_get_instruments,
+            commands::brokers::autotrade_set_failure_mode,
+            commands::brokers::autotrade_get_expiring_orders,
+            commands::brokers::autotrade_set_market_timezone,
+            commands::brokers::autotrade_replay,
+            commands::brokers::autotrade_get_time_and_sales,
+            commands::brokers::autotrade_estimate_slippage,
+            commands::brokers::autotrade_get_cash_drag,
+            commands::brokers::autotrade_set_host_override,
+            commands::brokers::autotrade_clear_host_override,
+            commands::brokers::autotrade_set_position_alert,
+            commands::brokers::autotrade_list_position_alerts,
+            commands::brokers::autotrade_remove_position_alert,
+            commands::brokers::autotrade_get_position_pnl_split,
+            commands::brokers::autotrade_find_wash_sales,
+            commands::brokers::autotrade_compare_to_benchmark,
+            commands::brokers::autotrade_recent_errors,
+            commands::brokers::autotrade_get_fill_stats,
+            commands::brokers::autotrade_set_failover_urls,
+            commands::brokers::autotrade_get_leverage,
+            commands::brokers::autotrade_set_leverage_limit,
+            commands::brokers::autotrade_validate_symbol,
+            commands::brokers::autotrade_get_sector_drift,
+            commands::brokers::autotrade_replace_order,
+            commands::brokers::autotrade_get_sortino,
+            commands::brokers::autotrade_subscribe_summary,
+            commands::brokers::autotrade_unsubscribe_summary,
+            commands::brokers::autotrade_export_performance,
+            commands::brokers::autotrade_max_position_size,
+            commands::brokers::autotrade_get_corporate_actions,
+            commands::brokers::autotrade_get_calmar,
+            commands::brokers::autotrade_find_orphaned_orders,
+            commands::brokers::autotrade_get_market_status,
+            commands::brokers::autotrade_get_dividend_calendar,
+            commands::brokers::autotrade_get_atr,
+            commands::brokers::autotrade_simulate_price,
+            commands::brokers::autotrade_stress_test,
+            commands::brokers::autotrade_get_implementation_shortfall,
+            commands::brokers::autotrade_save_config,
+            commands::brokers::autotrade_load_config,
+            commands::brokers::autotrade_get_portfolio_duration,
+            commands::brokers::autotrade_get_margin_call_price,
+            commands::brokers::autotrade_get_orders_with_fills,
+            commands::brokers::autotrade_get_weighted_expense_ratio,
+            commands::brokers::autotrade_get_unfilled_quantity,
+            commands::brokers::autotrade_verify_consistency,
+            commands::brokers::autotrade_get_rolling_returns,
+            commands::brokers::autotrade_get_pnl_in_currency,
+            commands::brokers::autotrade_check_pdt,
+            commands::brokers::autotrade_get_execution_quality,
+            commands::brokers::autotrade_set_position_note,
+            commands::brokers::autotrade_get_position_notes,
+            commands::brokers::autotrade_get_activity_heatmap,
+            commands::brokers::autotrade_get_correlation_matrix,
+            commands::brokers::autotrade_start_snapshot_schedule,
+            commands::brokers::autotrade_stop_snapshot_schedule,
+            commands::brokers::autotrade_get_information_ratio,
             // Generic Storage Commands (SQLite key-value storage)
             commands::storage::storage_set,
             commands::storage::storage_get,